@@ -2,26 +2,83 @@
 
 use eframe::egui;
 use image::GenericImageView;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{Receiver, Sender};
 
-struct LoadedImage {
-    path: PathBuf,
-    tiles: Vec<ImageTile>,
-    full_size: egui::Vec2,
-}
+const CACHE_CAPACITY: usize = 5;
 
+#[derive(Clone)]
 struct ImageTile {
     texture: egui::TextureHandle,
     rect: egui::Rect,
 }
 
+#[derive(Clone, Default)]
+struct ImageMetadata {
+    capture_date: Option<String>,
+    camera_make: Option<String>,
+    camera_model: Option<String>,
+    exposure: Option<String>,
+    iso: Option<String>,
+    focal_length: Option<String>,
+    gps: Option<String>,
+    width: u32,
+    height: u32,
+}
+
+/// One row in the file-browser overlay: either a subdirectory to navigate
+/// into or a supported image to open.
+struct BrowseEntry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+}
+
+enum LoadMsg {
+    TileReady {
+        path: PathBuf,
+        tile: ImageTile,
+        full_size: egui::Vec2,
+    },
+    LoadComplete {
+        path: PathBuf,
+        metadata: ImageMetadata,
+    },
+    CachePopulated {
+        path: PathBuf,
+        tiles: Vec<ImageTile>,
+        full_size: egui::Vec2,
+        metadata: ImageMetadata,
+    },
+    PrefetchSkipped {
+        path: PathBuf,
+    },
+    PrefetchFailed {
+        path: PathBuf,
+    },
+    AnimationReady {
+        path: PathBuf,
+        frames: Vec<(Vec<ImageTile>, std::time::Duration)>,
+        full_size: egui::Vec2,
+        metadata: ImageMetadata,
+        loop_limit: Option<u32>,
+    },
+}
+
+struct Animation {
+    frames: Vec<(Vec<ImageTile>, std::time::Duration)>,
+    current_frame: usize,
+    last_advance: std::time::Instant,
+    paused: bool,
+    loop_limit: Option<u32>,
+    loops_done: u32,
+    finished: bool,
+}
+
 fn main() -> eframe::Result {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: viewer <path_to_image>");
-        std::process::exit(1);
-    }
+    let initial_path = args.get(1).map(PathBuf::from);
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -33,7 +90,7 @@ fn main() -> eframe::Result {
     eframe::run_native(
         "ImgViewer",
         options,
-        Box::new(|cc| Ok(Box::new(LeanViewer::new(cc, PathBuf::from(&args[1]))))),
+        Box::new(|cc| Ok(Box::new(LeanViewer::new(cc, initial_path)))),
     )
 }
 
@@ -44,105 +101,583 @@ struct LeanViewer {
     zoom: f32,
     rotation_steps: i32,
     first_frame: bool,
-    current_path: PathBuf,
+    loading: bool,
+    current_path: Option<PathBuf>,
     album: Vec<PathBuf>,
-    rx: Receiver<LoadedImage>,
-    tx: Sender<LoadedImage>,
+    album_dir: Option<PathBuf>,
+    rx: Receiver<LoadMsg>,
+    tx: Sender<LoadMsg>,
     show_about: bool,
+    image_cache: HashMap<PathBuf, (Vec<ImageTile>, egui::Vec2, ImageMetadata)>,
+    cache_order: VecDeque<PathBuf>,
+    pending_prefetch: HashSet<PathBuf>,
+    current_metadata: Option<ImageMetadata>,
+    show_metadata: bool,
+    show_browser: bool,
+    browse_dir: PathBuf,
+    animation: Option<Animation>,
+    load_error: Option<PathBuf>,
+    #[cfg(feature = "profiling")]
+    show_profiler: bool,
+    #[cfg(feature = "profiling")]
+    last_frame_instant: std::time::Instant,
+    #[cfg(feature = "profiling")]
+    frame_time_ms: f32,
 }
 
 impl LeanViewer {
-    pub fn new(cc: &eframe::CreationContext<'_>, path: PathBuf) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, path: Option<PathBuf>) -> Self {
         let (tx, rx) = std::sync::mpsc::channel();
-        let (tiles, full_size, album) = Self::load_assets(&cc.egui_ctx, &path);
-        Self {
-            tiles,
-            full_size,
+        let browse_dir = path
+            .as_ref()
+            .and_then(|p| p.parent().map(PathBuf::from))
+            .or_else(Self::load_last_dir)
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        let album = path.as_ref().map(|p| Self::scan_album(p)).unwrap_or_default();
+        let album_dir = path.as_ref().and_then(|p| p.parent().map(PathBuf::from));
+
+        let mut viewer = Self {
+            tiles: Vec::new(),
+            full_size: egui::Vec2::ZERO,
             offset: egui::Vec2::ZERO,
             zoom: 1.0,
             rotation_steps: 0,
             first_frame: true,
-            current_path: path,
+            loading: path.is_some(),
+            current_path: path.clone(),
             album,
+            album_dir,
             rx,
             tx,
             show_about: false,
+            image_cache: HashMap::new(),
+            cache_order: VecDeque::new(),
+            pending_prefetch: HashSet::new(),
+            current_metadata: None,
+            show_metadata: false,
+            show_browser: path.is_none(),
+            browse_dir,
+            animation: None,
+            load_error: None,
+            #[cfg(feature = "profiling")]
+            show_profiler: false,
+            #[cfg(feature = "profiling")]
+            last_frame_instant: std::time::Instant::now(),
+            #[cfg(feature = "profiling")]
+            frame_time_ms: 0.0,
+        };
+        if let Some(path) = path {
+            viewer.spawn_decode(cc.egui_ctx.clone(), path);
         }
+        viewer
     }
 
-    fn load_assets(
-        ctx: &egui::Context,
-        path: &PathBuf,
-    ) -> (Vec<ImageTile>, egui::Vec2, Vec<PathBuf>) {
+    fn supported_extensions() -> &'static [&'static str] {
+        #[cfg(feature = "avif")]
+        const EXTENSIONS: &[&str] = &[
+            "jpg", "jpeg", "png", "webp", "bmp", "gif", "heic", "heif", "tiff", "tga", "avif",
+        ];
+        #[cfg(not(feature = "avif"))]
+        const EXTENSIONS: &[&str] = &[
+            "jpg", "jpeg", "png", "webp", "bmp", "gif", "heic", "heif", "tiff", "tga",
+        ];
+        EXTENSIONS
+    }
+
+    fn scan_album(path: &Path) -> Vec<PathBuf> {
+        let mut album = Vec::new();
+        if let Some(parent) = path.parent() {
+            album = Self::list_images(parent);
+        }
+        album
+    }
+
+    /// Lists the supported images directly inside `dir`, sorted by name.
+    fn list_images(dir: &Path) -> Vec<PathBuf> {
+        let mut images: Vec<PathBuf> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                let e = p
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+                Self::supported_extensions().contains(&e.as_str())
+            })
+            .collect();
+        images.sort();
+        images
+    }
+
+    /// Lists `dir`'s subdirectories and supported images for the file
+    /// browser overlay, directories first.
+    fn list_browse_entries(dir: &Path) -> Vec<BrowseEntry> {
+        let mut entries: Vec<BrowseEntry> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let path = e.path();
+                let is_dir = path.is_dir();
+                if !is_dir {
+                    let ext = path
+                        .extension()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("")
+                        .to_lowercase();
+                    if !Self::supported_extensions().contains(&ext.as_str()) {
+                        return None;
+                    }
+                }
+                let name = path.file_name()?.to_string_lossy().to_string();
+                Some(BrowseEntry { path, name, is_dir })
+            })
+            .collect();
+        entries.sort_by(|a, b| (!a.is_dir, &a.name).cmp(&(!b.is_dir, &b.name)));
+        entries
+    }
+
+    fn history_file() -> Option<PathBuf> {
+        Some(dirs::cache_dir()?.join("imgviewer").join("last_dir"))
+    }
+
+    fn load_last_dir() -> Option<PathBuf> {
+        let content = std::fs::read_to_string(Self::history_file()?).ok()?;
+        let dir = PathBuf::from(content.trim());
+        dir.is_dir().then_some(dir)
+    }
+
+    fn save_last_dir(dir: &Path) {
+        if let Some(history_file) = Self::history_file() {
+            if let Some(parent) = history_file.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let _ = std::fs::write(history_file, dir.display().to_string());
+        }
+    }
+
+    fn decode_source(path: &PathBuf) -> (image::DynamicImage, ImageMetadata) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
         let ext = path
             .extension()
             .and_then(|s| s.to_str())
             .unwrap_or("")
             .to_lowercase();
-        let img = if ext == "heic" || ext == "heif" {
-            Self::decode_heic(path).expect("HEIC decoding failed")
-        } else {
-            image::open(path).expect("Failed to open image")
+        let img = Self::decode_by_extension(&ext, path);
+
+        let exif = Self::read_exif(path);
+        let img = Self::apply_exif_orientation(img, exif.as_ref());
+        let (width, height) = img.dimensions();
+        let metadata = Self::read_metadata(exif.as_ref(), width, height);
+        (img, metadata)
+    }
+
+    fn read_exif(path: &PathBuf) -> Option<exif::Exif> {
+        let file = std::fs::File::open(path).ok()?;
+        let mut reader = std::io::BufReader::new(file);
+        exif::Reader::new().read_from_container(&mut reader).ok()
+    }
+
+    fn apply_exif_orientation(
+        img: image::DynamicImage,
+        exif: Option<&exif::Exif>,
+    ) -> image::DynamicImage {
+        let orientation = exif
+            .and_then(|e| e.get_field(exif::Tag::Orientation, exif::In::PRIMARY))
+            .and_then(|f| f.value.get_uint(0))
+            .unwrap_or(1);
+
+        match orientation {
+            2 => img.fliph(),
+            3 => img.rotate180(),
+            4 => img.flipv(),
+            5 => img.rotate90().fliph(),
+            6 => img.rotate90(),
+            7 => img.rotate270().fliph(),
+            8 => img.rotate270(),
+            _ => img,
+        }
+    }
+
+    fn read_metadata(exif: Option<&exif::Exif>, width: u32, height: u32) -> ImageMetadata {
+        let field = |tag| exif.and_then(|e| e.get_field(tag, exif::In::PRIMARY));
+        let display = |tag| {
+            exif.zip(field(tag))
+                .map(|(e, f)| f.display_value().with_unit(e).to_string())
+        };
+
+        ImageMetadata {
+            capture_date: display(exif::Tag::DateTimeOriginal),
+            camera_make: display(exif::Tag::Make),
+            camera_model: display(exif::Tag::Model),
+            exposure: display(exif::Tag::ExposureTime),
+            iso: display(exif::Tag::PhotographicSensitivity),
+            focal_length: display(exif::Tag::FocalLength),
+            gps: match (display(exif::Tag::GPSLatitude), display(exif::Tag::GPSLongitude)) {
+                (Some(lat), Some(lon)) => Some(format!("{lat}, {lon}")),
+                _ => None,
+            },
+            width,
+            height,
+        }
+    }
+
+    fn make_tile(
+        ctx: &egui::Context,
+        path: &PathBuf,
+        img: &image::DynamicImage,
+        x: u32,
+        y: u32,
+        tw: u32,
+        th: u32,
+    ) -> ImageTile {
+        let color_image = {
+            #[cfg(feature = "profiling")]
+            puffin::profile_scope!("tile_split");
+            let tile_view = img.view(x, y, tw, th).to_image();
+            egui::ColorImage::from_rgba_unmultiplied([tw as usize, th as usize], &tile_view)
         };
+        let tex_name = format!("{}_{}_{}", path.display(), x, y);
+        let texture = {
+            #[cfg(feature = "profiling")]
+            puffin::profile_scope!("load_texture");
+            ctx.load_texture(tex_name, color_image, egui::TextureOptions::LINEAR)
+        };
+        ImageTile {
+            texture,
+            rect: egui::Rect::from_min_size(
+                egui::pos2(x as f32, y as f32),
+                egui::vec2(tw as f32, th as f32),
+            ),
+        }
+    }
 
+    fn tile_image(ctx: &egui::Context, path: &PathBuf, img: &image::DynamicImage) -> Vec<ImageTile> {
         let (width, height) = img.dimensions();
         let tile_limit = 2048;
         let mut tiles = Vec::new();
-
         for y in (0..height).step_by(tile_limit) {
             for x in (0..width).step_by(tile_limit) {
                 let tw = (tile_limit as u32).min(width - x);
                 let th = (tile_limit as u32).min(height - y);
-                let tile_view = img.view(x, y, tw, th).to_image();
-                let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                    [tw as usize, th as usize],
-                    &tile_view,
-                );
-
-                let tex_name = format!("{}_{}_{}", path.display(), x, y);
-                let texture = ctx.load_texture(tex_name, color_image, egui::TextureOptions::LINEAR);
-
-                tiles.push(ImageTile {
-                    texture,
-                    rect: egui::Rect::from_min_size(
-                        egui::pos2(x as f32, y as f32),
-                        egui::vec2(tw as f32, th as f32),
-                    ),
-                });
+                tiles.push(Self::make_tile(ctx, path, img, x, y, tw, th));
             }
         }
+        tiles
+    }
 
-        let mut album = Vec::new();
+    fn spawn_decode(&self, ctx: egui::Context, path: PathBuf) {
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let ext = path
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+
+            if matches!(ext.as_str(), "gif" | "webp") {
+                if let Some((frames, full_size, metadata, loop_limit)) = Self::decode_animated(&ctx, &path, &ext) {
+                    let _ = tx.send(LoadMsg::AnimationReady {
+                        path,
+                        frames,
+                        full_size,
+                        metadata,
+                        loop_limit,
+                    });
+                    ctx.request_repaint();
+                    return;
+                }
+            }
+
+            #[cfg(feature = "profiling")]
+            puffin::profile_scope!("decode");
+            let (img, metadata) = Self::decode_source(&path);
+            let (width, height) = img.dimensions();
+            let full_size = egui::vec2(width as f32, height as f32);
+            let tile_limit = 2048;
+
+            {
+                #[cfg(feature = "profiling")]
+                puffin::profile_scope!("tile_and_upload");
+                for y in (0..height).step_by(tile_limit) {
+                    for x in (0..width).step_by(tile_limit) {
+                        let tw = (tile_limit as u32).min(width - x);
+                        let th = (tile_limit as u32).min(height - y);
+                        let tile = Self::make_tile(&ctx, &path, &img, x, y, tw, th);
+
+                        if tx
+                            .send(LoadMsg::TileReady {
+                                path: path.clone(),
+                                tile,
+                                full_size,
+                            })
+                            .is_err()
+                        {
+                            return;
+                        }
+                        ctx.request_repaint();
+                    }
+                }
+            }
+
+            let _ = tx.send(LoadMsg::LoadComplete { path, metadata });
+            ctx.request_repaint();
+        });
+    }
+
+    fn gif_loop_limit(path: &PathBuf) -> Option<u32> {
+        let file = std::fs::File::open(path).ok()?;
+        let decoder = image::codecs::gif::GifDecoder::new(file).ok()?;
+        match decoder.repeat() {
+            image::codecs::gif::Repeat::Finite(n) => Some(n as u32),
+            image::codecs::gif::Repeat::Infinite => None,
+        }
+    }
+
+    fn decode_animated(
+        ctx: &egui::Context,
+        path: &PathBuf,
+        ext: &str,
+    ) -> Option<(Vec<(Vec<ImageTile>, std::time::Duration)>, egui::Vec2, ImageMetadata, Option<u32>)> {
+        use image::AnimationDecoder;
+
+        let loop_limit = if ext == "gif" { Self::gif_loop_limit(path) } else { None };
+
+        let file = std::fs::File::open(path).ok()?;
+        let raw_frames: Vec<image::Frame> = match ext {
+            "gif" => image::codecs::gif::GifDecoder::new(file)
+                .ok()?
+                .into_frames()
+                .collect_frames()
+                .ok()?,
+            "webp" => image::codecs::webp::WebPDecoder::new(file)
+                .ok()?
+                .into_frames()
+                .collect_frames()
+                .ok()?,
+            _ => return None,
+        };
+        if raw_frames.len() <= 1 {
+            return None;
+        }
+
+        let (width, height) = raw_frames[0].buffer().dimensions();
+        let full_size = egui::vec2(width as f32, height as f32);
+        let metadata = Self::read_metadata(Self::read_exif(path).as_ref(), width, height);
+
+        let frames = raw_frames
+            .into_iter()
+            .map(|frame| {
+                let delay = frame.delay().into();
+                let img = image::DynamicImage::ImageRgba8(frame.into_buffer());
+                (Self::tile_image(ctx, path, &img), delay)
+            })
+            .collect();
+
+        Some((frames, full_size, metadata, loop_limit))
+    }
+
+    fn spawn_prefetch(&mut self, ctx: egui::Context, path: PathBuf) {
+        if self.image_cache.contains_key(&path) || self.pending_prefetch.contains(&path) {
+            return;
+        }
+        self.pending_prefetch.insert(path.clone());
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let ext = path
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+                if matches!(ext.as_str(), "gif" | "webp")
+                    && Self::decode_animated(&ctx, &path, &ext).is_some()
+                {
+                    return None;
+                }
+
+                let (img, metadata) = Self::decode_source(&path);
+                let (width, height) = img.dimensions();
+                let full_size = egui::vec2(width as f32, height as f32);
+                let tiles = Self::tile_image(&ctx, &path, &img);
+                Some((tiles, full_size, metadata))
+            }));
+
+            match result {
+                Ok(Some((tiles, full_size, metadata))) => {
+                    let _ = tx.send(LoadMsg::CachePopulated {
+                        path,
+                        tiles,
+                        full_size,
+                        metadata,
+                    });
+                }
+                Ok(None) => {
+                    let _ = tx.send(LoadMsg::PrefetchSkipped { path });
+                }
+                Err(_) => {
+                    let _ = tx.send(LoadMsg::PrefetchFailed { path });
+                }
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn prefetch_neighbors(&mut self, ctx: egui::Context) {
+        for delta in [1, -1] {
+            if let Some(path) = self.neighbor_path(delta) {
+                self.spawn_prefetch(ctx.clone(), path);
+            }
+        }
+    }
+
+    fn neighbor_path(&self, delta: i32) -> Option<PathBuf> {
+        let current = self.current_path.as_ref()?;
+        let pos = self.album.iter().position(|p| p == current)?;
+        let new_index = (pos as i32 + delta).rem_euclid(self.album.len() as i32) as usize;
+        Some(self.album[new_index].clone())
+    }
+
+    fn cache_insert(
+        &mut self,
+        path: PathBuf,
+        tiles: Vec<ImageTile>,
+        full_size: egui::Vec2,
+        metadata: ImageMetadata,
+    ) {
+        if self.image_cache.contains_key(&path) {
+            self.cache_order.retain(|p| p != &path);
+        } else if self.image_cache.len() >= CACHE_CAPACITY {
+            if let Some(oldest) = self.cache_order.pop_front() {
+                self.image_cache.remove(&oldest);
+            }
+        }
+        self.cache_order.push_back(path.clone());
+        self.image_cache.insert(path, (tiles, full_size, metadata));
+    }
+
+    fn cache_touch(&mut self, path: &PathBuf) {
+        self.cache_order.retain(|p| p != path);
+        self.cache_order.push_back(path.clone());
+    }
+
+    fn navigate(&mut self, ctx: egui::Context, delta: i32) {
+        if let Some(path) = self.neighbor_path(delta) {
+            self.load_path(ctx, path);
+        }
+    }
+
+    /// Opens `path` as the displayed image, routing through the cache and
+    /// background-decode path used for album navigation. Also refreshes the
+    /// album listing when `path` lives in a different directory (e.g. after
+    /// picking a file in the browser) and persists that directory as the
+    /// one to return to on next launch.
+    fn load_path(&mut self, ctx: egui::Context, path: PathBuf) {
         if let Some(parent) = path.parent() {
-            if let Ok(entries) = std::fs::read_dir(parent) {
-                album = entries
-                    .filter_map(|e| e.ok())
-                    .map(|e| e.path())
-                    .filter(|p| {
-                        let e = p
-                            .extension()
-                            .and_then(|s| s.to_str())
-                            .unwrap_or("")
-                            .to_lowercase();
-                        matches!(
-                            e.as_str(),
-                            "jpg"
-                                | "jpeg"
-                                | "png"
-                                | "webp"
-                                | "bmp"
-                                | "gif"
-                                | "heic"
-                                | "heif"
-                                | "tiff"
-                                | "tga"
-                        )
-                    })
-                    .collect();
-                album.sort();
+            if self.album_dir.as_deref() != Some(parent) {
+                self.album = Self::list_images(parent);
+                self.album_dir = Some(parent.to_path_buf());
+            }
+            self.browse_dir = parent.to_path_buf();
+            Self::save_last_dir(parent);
+        }
+
+        self.current_path = Some(path.clone());
+        self.offset = egui::Vec2::ZERO;
+        self.rotation_steps = 0;
+        self.first_frame = true;
+        self.show_browser = false;
+        self.animation = None;
+        self.load_error = None;
+
+        if let Some((tiles, full_size, metadata)) = self.image_cache.get(&path).cloned() {
+            self.tiles = tiles;
+            self.full_size = full_size;
+            self.current_metadata = Some(metadata);
+            self.loading = false;
+            self.cache_touch(&path);
+            self.announce_navigation(&ctx);
+            self.prefetch_neighbors(ctx);
+        } else {
+            self.tiles.clear();
+            self.full_size = egui::Vec2::ZERO;
+            self.loading = true;
+            if !self.pending_prefetch.contains(&path) {
+                self.spawn_decode(ctx, path);
             }
         }
-        (tiles, egui::vec2(width as f32, height as f32), album)
+    }
+
+    fn accessible_label(&self) -> String {
+        let Some(current) = &self.current_path else {
+            return "No image open".to_string();
+        };
+        let name = current
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| current.display().to_string());
+
+        match self.album.iter().position(|p| p == current) {
+            Some(pos) => format!(
+                "{name}, {} of {}, zoom {:.0}%, rotated {}°",
+                pos + 1,
+                self.album.len(),
+                self.zoom * 100.0,
+                self.rotation_steps * 90,
+            ),
+            None => name,
+        }
+    }
+
+    fn announce_navigation(&self, ctx: &egui::Context) {
+        let label = self.accessible_label();
+        ctx.output_mut(|o| {
+            o.events
+                .push(egui::output::OutputEvent::ValueChanged(egui::WidgetInfo::labeled(
+                    egui::WidgetType::Image,
+                    true,
+                    label,
+                )));
+        });
+    }
+
+    fn decode_by_extension(ext: &str, path: &PathBuf) -> image::DynamicImage {
+        match ext {
+            "heic" | "heif" => Self::decode_heic(path).expect("HEIC decoding failed"),
+            #[cfg(feature = "turbo")]
+            "jpg" | "jpeg" => Self::decode_turbojpeg(path).expect("turbojpeg decoding failed"),
+            #[cfg(feature = "avif")]
+            "avif" => Self::decode_avif(path).expect("AVIF decoding failed"),
+            _ => image::open(path).expect("Failed to open image"),
+        }
+    }
+
+    #[cfg(feature = "turbo")]
+    fn decode_turbojpeg(path: &PathBuf) -> Result<image::DynamicImage, Box<dyn std::error::Error>> {
+        let data = std::fs::read(path)?;
+        let image: turbojpeg::Image<Vec<u8>> = turbojpeg::decompress(&data, turbojpeg::PixelFormat::RGBA)?;
+        let buffer = image::RgbaImage::from_raw(image.width as u32, image.height as u32, image.pixels)
+            .ok_or("Buffer fail")?;
+        Ok(image::DynamicImage::ImageRgba8(buffer))
+    }
+
+    #[cfg(feature = "avif")]
+    fn decode_avif(path: &PathBuf) -> Result<image::DynamicImage, Box<dyn std::error::Error>> {
+        let data = std::fs::read(path)?;
+        let decoded = avif_decode::Decoder::from_avif(&data)?.to_image()?;
+        match decoded {
+            avif_decode::Image::Rgba8(img) => {
+                let (width, height) = (img.width() as u32, img.height() as u32);
+                let bytes: Vec<u8> = img.buf().iter().flat_map(|p| [p.r, p.g, p.b, p.a]).collect();
+                let buffer = image::RgbaImage::from_raw(width, height, bytes).ok_or("Buffer fail")?;
+                Ok(image::DynamicImage::ImageRgba8(buffer))
+            }
+            _ => Err("Unsupported AVIF pixel format".into()),
+        }
     }
 
     fn decode_heic(path: &PathBuf) -> Result<image::DynamicImage, Box<dyn std::error::Error>> {
@@ -158,50 +693,146 @@ impl LeanViewer {
         let height = image.height();
         let interleaved = image.planes().interleaved.ok_or("No interleaved plane")?;
         let mut rgba_data = Vec::with_capacity((width * height * 4) as usize);
-        for y in 0..height {
-            let start = (y as usize) * interleaved.stride;
-            let end = start + (width as usize) * 4;
-            rgba_data.extend_from_slice(&interleaved.data[start..end]);
+        {
+            #[cfg(feature = "profiling")]
+            puffin::profile_scope!("heic_plane_copy");
+            for y in 0..height {
+                let start = (y as usize) * interleaved.stride;
+                let end = start + (width as usize) * 4;
+                rgba_data.extend_from_slice(&interleaved.data[start..end]);
+            }
         }
         let buffer = image::RgbaImage::from_raw(width, height, rgba_data).ok_or("Buffer fail")?;
         Ok(image::DynamicImage::ImageRgba8(buffer))
     }
+}
+
+impl eframe::App for LeanViewer {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        #[cfg(feature = "profiling")]
+        {
+            puffin::GlobalProfiler::lock().new_frame();
+            let now = std::time::Instant::now();
+            self.frame_time_ms = now.duration_since(self.last_frame_instant).as_secs_f32() * 1000.0;
+            self.last_frame_instant = now;
+        }
 
-    fn preload(&self, ctx: egui::Context, delta: i32) {
-        if let Some(pos) = self.album.iter().position(|p| p == &self.current_path) {
-            let new_index = (pos as i32 + delta).rem_euclid(self.album.len() as i32) as usize;
-            let path = self.album[new_index].clone();
-            let tx = self.tx.clone();
-            std::thread::spawn(move || {
-                let (tiles, full_size, _) = Self::load_assets(&ctx, &path);
-                let _ = tx.send(LoadedImage {
+        while let Ok(msg) = self.rx.try_recv() {
+            match msg {
+                LoadMsg::TileReady {
+                    path,
+                    tile,
+                    full_size,
+                } => {
+                    if self.current_path.as_deref() == Some(path.as_path()) {
+                        self.tiles.push(tile);
+                        self.full_size = full_size;
+                    }
+                }
+                LoadMsg::LoadComplete { path, metadata } => {
+                    if self.current_path.as_deref() == Some(path.as_path()) {
+                        self.loading = false;
+                        self.current_metadata = Some(metadata.clone());
+                        self.cache_insert(path, self.tiles.clone(), self.full_size, metadata);
+                        self.announce_navigation(ctx);
+                        self.prefetch_neighbors(ctx.clone());
+                    }
+                }
+                LoadMsg::CachePopulated {
                     path,
                     tiles,
                     full_size,
-                });
-                ctx.request_repaint();
-            });
+                    metadata,
+                } => {
+                    self.pending_prefetch.remove(&path);
+                    if self.loading && self.current_path.as_deref() == Some(path.as_path()) {
+                        self.tiles = tiles.clone();
+                        self.full_size = full_size;
+                        self.current_metadata = Some(metadata.clone());
+                        self.loading = false;
+                        self.announce_navigation(ctx);
+                        self.prefetch_neighbors(ctx.clone());
+                    }
+                    self.cache_insert(path, tiles, full_size, metadata);
+                }
+                LoadMsg::PrefetchSkipped { path } => {
+                    self.pending_prefetch.remove(&path);
+                    if self.loading
+                        && self.tiles.is_empty()
+                        && self.current_path.as_deref() == Some(path.as_path())
+                    {
+                        self.spawn_decode(ctx.clone(), path);
+                    }
+                }
+                LoadMsg::PrefetchFailed { path } => {
+                    self.pending_prefetch.remove(&path);
+                    if self.loading && self.current_path.as_deref() == Some(path.as_path()) {
+                        self.loading = false;
+                        self.load_error = Some(path);
+                    }
+                }
+                LoadMsg::AnimationReady {
+                    path,
+                    frames,
+                    full_size,
+                    metadata,
+                    loop_limit,
+                } => {
+                    if self.current_path.as_deref() == Some(path.as_path()) {
+                        self.loading = false;
+                        self.full_size = full_size;
+                        self.current_metadata = Some(metadata);
+                        self.tiles = frames[0].0.clone();
+                        self.animation = Some(Animation {
+                            frames,
+                            current_frame: 0,
+                            last_advance: std::time::Instant::now(),
+                            paused: false,
+                            loop_limit,
+                            loops_done: 0,
+                            finished: false,
+                        });
+                        self.announce_navigation(ctx);
+                        self.prefetch_neighbors(ctx.clone());
+                    }
+                }
+            }
         }
-    }
-}
 
-impl eframe::App for LeanViewer {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        if let Ok(loaded) = self.rx.try_recv() {
-            self.tiles = loaded.tiles;
-            self.full_size = loaded.full_size;
-            self.current_path = loaded.path;
-            self.offset = egui::Vec2::ZERO;
-            self.rotation_steps = 0;
-            self.first_frame = true;
+        if let Some(anim) = &mut self.animation {
+            if !anim.paused && !anim.finished {
+                let current_delay = anim.frames[anim.current_frame].1;
+                if anim.last_advance.elapsed() >= current_delay {
+                    anim.last_advance = std::time::Instant::now();
+                    let next_frame = anim.current_frame + 1;
+                    if next_frame >= anim.frames.len() {
+                        anim.loops_done += 1;
+                        if anim.loop_limit.is_some_and(|limit| anim.loops_done >= limit) {
+                            anim.finished = true;
+                        } else {
+                            anim.current_frame = 0;
+                            self.tiles = anim.frames[anim.current_frame].0.clone();
+                        }
+                    } else {
+                        anim.current_frame = next_frame;
+                        self.tiles = anim.frames[anim.current_frame].0.clone();
+                    }
+                }
+                if !anim.finished {
+                    let remaining = anim.frames[anim.current_frame]
+                        .1
+                        .saturating_sub(anim.last_advance.elapsed());
+                    ctx.request_repaint_after(remaining);
+                }
+            }
         }
 
         ctx.input(|i| {
             if i.key_pressed(egui::Key::ArrowRight) {
-                self.preload(ctx.clone(), 1);
+                self.navigate(ctx.clone(), 1);
             }
             if i.key_pressed(egui::Key::ArrowLeft) {
-                self.preload(ctx.clone(), -1);
+                self.navigate(ctx.clone(), -1);
             }
             if i.key_pressed(egui::Key::Escape) {
                 std::process::exit(0);
@@ -209,6 +840,18 @@ impl eframe::App for LeanViewer {
             if i.key_pressed(egui::Key::R) {
                 self.rotation_steps = (self.rotation_steps + 1) % 4;
             }
+            if i.key_pressed(egui::Key::I) {
+                self.show_metadata = !self.show_metadata;
+            }
+            if i.key_pressed(egui::Key::B) {
+                self.show_browser = !self.show_browser;
+            }
+            if i.key_pressed(egui::Key::Space) {
+                if let Some(anim) = &mut self.animation {
+                    anim.paused = !anim.paused;
+                    anim.last_advance = std::time::Instant::now();
+                }
+            }
         });
 
         egui::CentralPanel::default()
@@ -219,12 +862,16 @@ impl eframe::App for LeanViewer {
                 let effective_size = if is_sideways { egui::vec2(self.full_size.y, self.full_size.x) } else { self.full_size };
 
                 let fit_zoom = (display_rect.width() / effective_size.x).min(display_rect.height() / effective_size.y);
-                if self.first_frame && display_rect.width() > 1.0 {
+                if self.first_frame && display_rect.width() > 1.0 && effective_size.x > 0.0 {
                     self.zoom = fit_zoom;
                     self.first_frame = false;
                 }
 
                 let (_rect, response) = ui.allocate_at_least(ui.available_size(), egui::Sense::click_and_drag());
+                let accessible_label = self.accessible_label();
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Image, true, accessible_label.clone())
+                });
                 if response.double_clicked() {
                     if (self.zoom - fit_zoom).abs() < 0.01 { self.zoom = 1.0; } else { self.zoom = fit_zoom; }
                     self.offset = egui::Vec2::ZERO;
@@ -247,7 +894,14 @@ impl eframe::App for LeanViewer {
                 });
 
                 response.context_menu(|ui| {
+                    if ui.button("Open…").clicked() { self.show_browser = true; ui.close_kind(egui::UiKind::Menu); }
+                    if ui.button("Image Info").clicked() { self.show_metadata = true; ui.close_kind(egui::UiKind::Menu); }
                     if ui.button("About").clicked() { self.show_about = true; ui.close_kind(egui::UiKind::Menu); }
+                    #[cfg(feature = "profiling")]
+                    {
+                        ui.separator();
+                        if ui.button("Profiler").clicked() { self.show_profiler = true; ui.close_kind(egui::UiKind::Menu); }
+                    }
                     ui.separator();
                     if ui.button("Exit").clicked() { std::process::exit(0); }
                 });
@@ -256,23 +910,53 @@ impl eframe::App for LeanViewer {
                 let rotation_angle = self.rotation_steps as f32 * std::f32::consts::FRAC_PI_2;
                 let rot = egui::emath::Rot2::from_angle(rotation_angle);
 
-                for tile in &self.tiles {
-                    let tile_size = tile.rect.size() * self.zoom;
+                {
+                    #[cfg(feature = "profiling")]
+                    puffin::profile_scope!("tile_draw_loop");
+                    for tile in &self.tiles {
+                        let tile_size = tile.rect.size() * self.zoom;
+
+                        // Convert relative position to Vec2 before rotation
+                        let tile_rel_to_center = (tile.rect.center() - (self.full_size / 2.0)).to_vec2();
+                        let rotated_pos = rot * tile_rel_to_center;
 
-                    // Convert relative position to Vec2 before rotation
-                    let tile_rel_to_center = (tile.rect.center() - (self.full_size / 2.0)).to_vec2();
-                    let rotated_pos = rot * tile_rel_to_center;
+                        let rect = egui::Rect::from_center_size(center + rotated_pos * self.zoom, tile_size);
 
-                    let rect = egui::Rect::from_center_size(center + rotated_pos * self.zoom, tile_size);
+                        let mut mesh = egui::Mesh::with_texture(tile.texture.id());
+                        mesh.add_rect_with_uv(
+                            rect,
+                            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                            egui::Color32::WHITE,
+                        );
+                        mesh.rotate(rot, rect.center());
+                        ui.painter().add(mesh);
+                    }
+                }
 
-                    let mut mesh = egui::Mesh::with_texture(tile.texture.id());
-                    mesh.add_rect_with_uv(
-                        rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                if self.loading && self.tiles.is_empty() {
+                    ui.painter().text(
+                        display_rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "Loading…",
+                        egui::FontId::proportional(24.0),
+                        egui::Color32::WHITE,
+                    );
+                } else if self.load_error.is_some() && self.tiles.is_empty() {
+                    ui.painter().text(
+                        display_rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "Failed to load image",
+                        egui::FontId::proportional(24.0),
+                        egui::Color32::WHITE,
+                    );
+                } else if self.current_path.is_none() {
+                    ui.painter().text(
+                        display_rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "Press B to open a file",
+                        egui::FontId::proportional(24.0),
                         egui::Color32::WHITE,
                     );
-                    mesh.rotate(rot, rect.center());
-                    ui.painter().add(mesh);
                 }
 
                 if self.show_about {
@@ -291,14 +975,120 @@ impl eframe::App for LeanViewer {
                                     ui.label(egui::RichText::new("Keyboard shortcuts:").strong());
                                     ui.label("• F: Toggle Zoom ( fit / 100% )");
                                     ui.label("• R: Rotate 90° clockwise");
+                                    ui.label("• I: Toggle image info");
                                     ui.label("• ESC: Exit");
                                     ui.label("• Arrows: Navigate album");
+                                    ui.label("• B: Toggle file browser");
+                                    ui.label("• Space: Pause / resume animation");
                                 });
                                 ui.add_space(10.0);
                                 if ui.button("Close").clicked() { self.show_about = false; }
                             });
                         });
                 }
+
+                if self.show_metadata {
+                    egui::Window::new("Image Info")
+                        .collapsible(false).resizable(false)
+                        .pivot(egui::Align2::CENTER_CENTER)
+                        .default_pos(display_rect.center())
+                        .show(ctx, |ui| {
+                            ui.vertical_centered(|ui| {
+                                ui.heading(
+                                    self.current_path
+                                        .as_ref()
+                                        .and_then(|p| p.file_name())
+                                        .map(|n| n.to_string_lossy().to_string())
+                                        .unwrap_or_default(),
+                                );
+                                ui.separator();
+                                match &self.current_metadata {
+                                    Some(m) => {
+                                        ui.vertical(|ui| {
+                                            ui.label(format!("Dimensions: {} x {}", m.width, m.height));
+                                            ui.label(format!("Captured: {}", m.capture_date.as_deref().unwrap_or("—")));
+                                            ui.label(format!("Camera: {} {}", m.camera_make.as_deref().unwrap_or(""), m.camera_model.as_deref().unwrap_or("—")));
+                                            ui.label(format!("Exposure: {}", m.exposure.as_deref().unwrap_or("—")));
+                                            ui.label(format!("ISO: {}", m.iso.as_deref().unwrap_or("—")));
+                                            ui.label(format!("Focal length: {}", m.focal_length.as_deref().unwrap_or("—")));
+                                            ui.label(format!("GPS: {}", m.gps.as_deref().unwrap_or("—")));
+                                        });
+                                    }
+                                    None => {
+                                        ui.label("No metadata available.");
+                                    }
+                                }
+                                ui.add_space(10.0);
+                                if ui.button("Close").clicked() { self.show_metadata = false; }
+                            });
+                        });
+                }
+
+                #[cfg(feature = "profiling")]
+                if self.show_profiler {
+                    let tile_count = self.tiles.len();
+                    let texture_bytes: usize = self
+                        .tiles
+                        .iter()
+                        .map(|t| (t.rect.width() * t.rect.height()) as usize * 4)
+                        .sum();
+                    egui::Window::new("Profiler")
+                        .default_pos(display_rect.left_top())
+                        .show(ctx, |ui| {
+                            ui.label(format!(
+                                "Frame time: {:.2} ms ({:.0} FPS)",
+                                self.frame_time_ms,
+                                if self.frame_time_ms > 0.0 { 1000.0 / self.frame_time_ms } else { 0.0 }
+                            ));
+                            ui.label(format!("Tiles: {tile_count}"));
+                            ui.label(format!("Texture memory: {:.1} MiB", texture_bytes as f64 / (1024.0 * 1024.0)));
+                            ui.separator();
+                            if ui.button("Close").clicked() { self.show_profiler = false; }
+                        });
+                    puffin_egui::profiler_window(ctx);
+                }
+
+                if self.show_browser {
+                    let mut open = true;
+                    let mut chosen = None;
+                    egui::Window::new("Open")
+                        .collapsible(false)
+                        .resizable(true)
+                        .default_size(egui::vec2(420.0, 480.0))
+                        .pivot(egui::Align2::CENTER_CENTER)
+                        .default_pos(display_rect.center())
+                        .open(&mut open)
+                        .show(ctx, |ui| {
+                            ui.label(egui::RichText::new(self.browse_dir.display().to_string()).strong());
+                            ui.separator();
+                            egui::ScrollArea::vertical().show(ui, |ui| {
+                                if let Some(parent) = self.browse_dir.parent() {
+                                    if ui.selectable_label(false, "⬆ ..").clicked() {
+                                        self.browse_dir = parent.to_path_buf();
+                                    }
+                                }
+                                for entry in Self::list_browse_entries(&self.browse_dir) {
+                                    let label = if entry.is_dir {
+                                        format!("📁 {}", entry.name)
+                                    } else {
+                                        format!("🖼 {}", entry.name)
+                                    };
+                                    if ui.selectable_label(false, label).clicked() {
+                                        if entry.is_dir {
+                                            self.browse_dir = entry.path;
+                                        } else {
+                                            chosen = Some(entry.path);
+                                        }
+                                    }
+                                }
+                            });
+                        });
+                    if let Some(path) = chosen {
+                        self.load_path(ctx.clone(), path);
+                    } else {
+                        self.show_browser = open;
+                    }
+                }
             });
     }
 }